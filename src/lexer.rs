@@ -1,114 +1,224 @@
+use std::borrow::Cow;
 use std::iter::{Peekable};
-use std::str::Chars;
+use std::str::CharIndices;
 
 #[derive(Eq, PartialEq)]
 #[derive(Debug)]
-pub(crate) enum Token {
+pub(crate) enum Token<'src> {
     Let,
     Function,
-    Identifier(String),
-    String(String),
-    Number(String),
+    IntType,
+    FloatType,
+    StringType,
+    BoolType,
+    Identifier(&'src str),
+    // Borrowed for the common case; owned only when the literal contains an
+    // escape sequence and the decoded contents diverge from the source bytes.
+    String(Cow<'src, str>),
+    Number(&'src str),
     Whitespace,
     NewLine,
     Equals,
     Plus,
+    Minus,
+    LeftParen,
+    RightParen,
+    Colon,
     SemiColon,
     EOF,
 }
 
+pub(crate) type Span = (usize, usize);
+
 #[derive(Eq, PartialEq)]
 #[derive(Debug)]
-enum LexingError {
+pub(crate) enum LexingError {
     UnexpectedCharacter { expected: char, actual: char },
-    InvalidChar(char),
+    InvalidChar { ch: char, position: usize },
     UnexpectedEndOfInput,
+    MalformedEscapeSequence(char),
+    InvalidUnicodeEscape,
+}
+
+pub(crate) struct Lexer<'src> {
+    source: &'src str,
+    chars: Peekable<CharIndices<'src>>,
+    len: usize,
+}
+
+impl<'src> Lexer<'src> {
+    pub(crate) fn new(input: &'src str) -> Self {
+        Lexer { source: input, chars: input.char_indices().peekable(), len: input.len() }
+    }
+
+    pub(crate) fn next_token(&mut self) -> Result<(Token<'src>, Span), LexingError> {
+        let start = self.chars.peek().map(|(pos, _)| *pos).unwrap_or(self.len);
+
+        match parse_next_token(self.source, &mut self.chars) {
+            Err(err) => Err(err),
+            Ok(Token::EOF) => Ok((Token::EOF, (self.len, self.len))),
+            Ok(token) => {
+                let end = self.chars.peek().map(|(pos, _)| *pos).unwrap_or(self.len);
+                Ok((token, (start, end)))
+            }
+        }
+    }
 }
 
-fn lex(input: &str) -> Result<Vec<Token>, LexingError> {
+fn lex<'src>(input: &'src str) -> Result<Vec<(Token<'src>, Span)>, LexingError> {
     let mut output = vec!();
-    let mut chars = input.chars().peekable();
+    let mut lexer = Lexer::new(input);
 
     loop {
-        match parse_next_token(&mut chars) {
+        match lexer.next_token() {
             Err(err) => return Err(err),
-            Ok(Token::EOF) => {
-                output.push(Token::EOF);
+            Ok((Token::EOF, span)) => {
+                output.push((Token::EOF, span));
                 break;
             }
-            Ok(token) => output.push(token),
+            Ok(token_and_span) => output.push(token_and_span),
         }
     }
 
     return Result::Ok(output);
 }
 
-type LexingResult = Result<Token, LexingError>;
+type LexingResult<'src> = Result<Token<'src>, LexingError>;
 
-fn parse_next_token(input: &mut Peekable<Chars>) -> LexingResult {
-    match input.peek() {
+fn parse_next_token<'src>(source: &'src str, chars: &mut Peekable<CharIndices<'src>>) -> LexingResult<'src> {
+    match chars.peek() {
         None => Ok(Token::EOF),
-        Some(next_char) => {
+        Some((position, next_char)) => {
+            let position = *position;
             match next_char {
-                ' ' => consume(input, Token::Whitespace),
-                '\t' => consume(input, Token::Whitespace),
-                '=' => consume(input, Token::Equals),
-                '+' => consume(input, Token::Plus),
-                '\n' => consume(input, Token::NewLine),
-                ';' => consume(input, Token::SemiColon),
-                '"' => parse_string(input),
-                '0'..='9' => parse_number(input),
-                'A'..='z' => parse_identifier_or_keyword(input),
-                unexpected => Result::Err(LexingError::InvalidChar(*unexpected))
+                ' ' => consume(chars, Token::Whitespace),
+                '\t' => consume(chars, Token::Whitespace),
+                '=' => consume(chars, Token::Equals),
+                '+' => consume(chars, Token::Plus),
+                '-' => consume(chars, Token::Minus),
+                '(' => consume(chars, Token::LeftParen),
+                ')' => consume(chars, Token::RightParen),
+                ':' => consume(chars, Token::Colon),
+                '\n' => consume(chars, Token::NewLine),
+                ';' => consume(chars, Token::SemiColon),
+                '"' => parse_string(source, chars),
+                '0'..='9' => parse_number(source, chars),
+                'A'..='z' => Ok(parse_identifier_or_keyword(source, chars)),
+                unexpected => Result::Err(LexingError::InvalidChar { ch: *unexpected, position })
             }
         }
     }
 }
 
-fn parse_string(input: &mut Peekable<Chars>) -> LexingResult {
-    return expect_next(input, '"')
-        .and_then(|_| {
-            let string_contents = take_while(input, |c| c != '"');
-            Ok(Token::String(stringify(string_contents)))
+fn parse_string<'src>(source: &'src str, chars: &mut Peekable<CharIndices<'src>>) -> LexingResult<'src> {
+    return expect_next(chars, '"')
+        .and_then(|_| parse_string_contents(source, chars))
+        .and_then(|contents| expect_next(chars, '"').map(|_| Token::String(contents)));
+}
+
+fn parse_string_contents<'src>(source: &'src str, chars: &mut Peekable<CharIndices<'src>>) -> Result<Cow<'src, str>, LexingError> {
+    let start = chars.peek().map(|(pos, _)| *pos).unwrap_or(source.len());
+
+    loop {
+        match chars.peek().copied() {
+            None => return Err(LexingError::UnexpectedEndOfInput),
+            Some((pos, '"')) => return Ok(Cow::Borrowed(&source[start..pos])),
+            Some((_, '\\')) => return parse_escaped_string_contents(source, chars, start).map(Cow::Owned),
+            Some(_) => { chars.next(); }
+        }
+    }
+}
+
+fn parse_escaped_string_contents(source: &str, chars: &mut Peekable<CharIndices>, start: usize) -> Result<String, LexingError> {
+    let escape_start = chars.peek().map(|(pos, _)| *pos).unwrap_or(source.len());
+    let mut contents = String::from(&source[start..escape_start]);
+
+    while let Some((_, c)) = chars.peek().copied() {
+        if c == '"' {
+            break;
+        }
+
+        chars.next();
+
+        if c == '\\' {
+            match parse_escape_sequence(chars) {
+                Err(err) => return Err(err),
+                Ok(escaped) => contents.push(escaped),
+            }
+        } else {
+            contents.push(c);
+        }
+    }
+
+    Ok(contents)
+}
+
+fn parse_escape_sequence(input: &mut Peekable<CharIndices>) -> Result<char, LexingError> {
+    match input.next() {
+        None => Err(LexingError::UnexpectedEndOfInput),
+        Some((_, 'n')) => Ok('\n'),
+        Some((_, 't')) => Ok('\t'),
+        Some((_, 'r')) => Ok('\r'),
+        Some((_, '\\')) => Ok('\\'),
+        Some((_, '"')) => Ok('"'),
+        Some((_, 'u')) => parse_unicode_escape(input),
+        Some((_, other)) => Err(LexingError::MalformedEscapeSequence(other)),
+    }
+}
+
+fn parse_unicode_escape(input: &mut Peekable<CharIndices>) -> Result<char, LexingError> {
+    expect_next(input, '{')
+        .map(|_| take_while(input, |c| c != '}'))
+        .and_then(|digits| expect_next(input, '}').map(|_| digits))
+        .and_then(|digits| {
+            u32::from_str_radix(&stringify(digits), 16)
+                .ok()
+                .and_then(char::from_u32)
+                .ok_or(LexingError::InvalidUnicodeEscape)
         })
-        .and_then(|res| expect_next(input, '"').map(|_| res));
 }
 
-fn consume(input: &mut Peekable<Chars>, token: Token) -> Result<Token, LexingError> {
-    input.next();
+fn consume<'src>(chars: &mut Peekable<CharIndices<'src>>, token: Token<'src>) -> LexingResult<'src> {
+    chars.next();
     return Ok(token);
 }
 
-fn parse_number(input: &mut Peekable<Chars>) -> Result<Token, LexingError> {
-    let mut num_contents = take_while(input, |c| c.is_digit(10));
+fn parse_number<'src>(source: &'src str, chars: &mut Peekable<CharIndices<'src>>) -> LexingResult<'src> {
+    let start = chars.peek().map(|(pos, _)| *pos).unwrap_or(source.len());
+    consume_while(chars, |c| c.is_digit(10));
 
-    if let Some('.') = input.peek() {
-        input.next();
-        num_contents.push('.');
-        num_contents.append(&mut take_while(input, |c| c.is_digit(10)));
+    if let Some((_, '.')) = chars.peek() {
+        chars.next();
+        consume_while(chars, |c| c.is_digit(10));
     }
 
-    if num_contents.is_empty() {
+    let end = chars.peek().map(|(pos, _)| *pos).unwrap_or(source.len());
+
+    if end == start {
         Err(LexingError::UnexpectedEndOfInput)
     } else {
-        Ok(Token::Number(stringify(num_contents)))
+        Ok(Token::Number(&source[start..end]))
     }
 }
 
-fn parse_identifier_or_keyword(input: &mut Peekable<Chars>) -> Result<Token, LexingError> {
-    let identifier_or_keyword = take_while(input, |c| c.is_alphanumeric());
+fn parse_identifier_or_keyword<'src>(source: &'src str, chars: &mut Peekable<CharIndices<'src>>) -> Token<'src> {
+    let start = chars.peek().map(|(pos, _)| *pos).unwrap_or(source.len());
+    consume_while(chars, |c| c.is_alphanumeric());
+    let end = chars.peek().map(|(pos, _)| *pos).unwrap_or(source.len());
 
-    let token = match stringify(identifier_or_keyword).as_str() {
+    match &source[start..end] {
         "let" => Token::Let,
         "function" => Token::Function,
-        name => Token::Identifier(name.to_string())
-    };
-
-    Ok(token)
+        "int" => Token::IntType,
+        "float" => Token::FloatType,
+        "string" => Token::StringType,
+        "bool" => Token::BoolType,
+        name => Token::Identifier(name),
+    }
 }
 
-fn expect_next(input: &mut Peekable<Chars>, expected_char: char) -> Result<(), LexingError> {
-    if let Some(c) = input.next() {
+fn expect_next(input: &mut Peekable<CharIndices>, expected_char: char) -> Result<(), LexingError> {
+    if let Some((_, c)) = input.next() {
         if c == expected_char {
             Ok(())
         } else {
@@ -119,9 +229,9 @@ fn expect_next(input: &mut Peekable<Chars>, expected_char: char) -> Result<(), L
     }
 }
 
-fn test_word_or_rewind<'a>(input: &mut Peekable<Chars>, word: &'a str) -> Option<&'a str> {
+fn test_word_or_rewind<'a>(input: &mut Peekable<CharIndices>, word: &'a str) -> Option<&'a str> {
     for c in word.chars() {
-        if let Some(peeked_char) = input.peek() {
+        if let Some((_, peeked_char)) = input.peek() {
             if peeked_char == &c {
                 return Option::None;
             }
@@ -130,10 +240,14 @@ fn test_word_or_rewind<'a>(input: &mut Peekable<Chars>, word: &'a str) -> Option
     return Some(word);
 }
 
-fn take_while(input: &mut Peekable<Chars>, predicate: impl Fn(char) -> bool) -> Vec<char> {
+fn consume_while(input: &mut Peekable<CharIndices>, predicate: impl Fn(char) -> bool) {
+    while input.next_if(|(_, c)| predicate(*c)).is_some() {}
+}
+
+fn take_while(input: &mut Peekable<CharIndices>, predicate: impl Fn(char) -> bool) -> Vec<char> {
     let mut output: Vec<char> = vec![];
 
-    while let Some(c) = input.next_if(|c| predicate(*c)) {
+    while let Some((_, c)) = input.next_if(|(_, c)| predicate(*c)) {
         output.push(c)
     }
 
@@ -148,18 +262,23 @@ fn stringify(chars: Vec<char>) -> String {
 
 #[cfg(test)]
 mod tests {
+    use std::borrow::Cow;
     use crate::lexer::{lex, LexingError, Token};
 
     #[test]
     fn empty_returns_eof() {
         let result = lex(&"");
-        assert_eq!(result.unwrap(), vec![Token::EOF])
+        assert_eq!(result.unwrap(), vec![(Token::EOF, (0, 0))])
     }
 
     #[test]
     fn space_and_tab_are_whitespace() {
         let result = lex(&" \t");
-        assert_eq!(result.unwrap(), vec![Token::Whitespace, Token::Whitespace, Token::EOF])
+        assert_eq!(result.unwrap(), vec![
+            (Token::Whitespace, (0, 1)),
+            (Token::Whitespace, (1, 2)),
+            (Token::EOF, (2, 2)),
+        ])
     }
 
     #[test]
@@ -168,15 +287,50 @@ mod tests {
         let output = lex(&input);
 
         assert_eq!(output.unwrap(), vec![
-            Token::String(String::from("wow")),
-            Token::Whitespace,
-            Token::String(String::from("this")),
-            Token::Whitespace,
-            Token::String(String::from("string")),
-            Token::EOF,
+            (Token::String(Cow::Borrowed("wow")), (0, 5)),
+            (Token::Whitespace, (5, 6)),
+            (Token::String(Cow::Borrowed("this")), (6, 12)),
+            (Token::Whitespace, (12, 13)),
+            (Token::String(Cow::Borrowed("string")), (13, 21)),
+            (Token::EOF, (21, 21)),
         ])
     }
 
+    #[test]
+    fn string_with_common_escapes_is_decoded() {
+        let input = "\"line\\nbreak\\ttab\\\"quote\\\\backslash\"";
+        let output = lex(&input);
+
+        assert_eq!(
+            output.unwrap()[0].0,
+            Token::String(Cow::Borrowed("line\nbreak\ttab\"quote\\backslash")),
+        );
+    }
+
+    #[test]
+    fn string_with_unicode_escape_is_decoded() {
+        let input = "\"\\u{1F600}\"";
+        let output = lex(&input);
+
+        assert_eq!(output.unwrap()[0].0, Token::String(Cow::Borrowed("\u{1F600}")));
+    }
+
+    #[test]
+    fn string_with_unrecognized_escape_fails() {
+        let input = "\"\\q\"";
+        let output = lex(&input);
+
+        assert_eq!(output.unwrap_err(), LexingError::MalformedEscapeSequence('q'));
+    }
+
+    #[test]
+    fn string_with_invalid_unicode_escape_fails() {
+        let input = "\"\\u{D800}\"";
+        let output = lex(&input);
+
+        assert_eq!(output.unwrap_err(), LexingError::InvalidUnicodeEscape);
+    }
+
     #[test]
     fn string_missing_terminator_fails() {
         let input = "\"wow this is a string";
@@ -193,14 +347,14 @@ mod tests {
         assert_eq!(
             output.unwrap(),
             vec![
-                Token::Number(String::from("1")),
-                Token::Whitespace,
-                Token::Number(String::from("2")),
-                Token::Whitespace,
-                Token::Number(String::from("3")),
-                Token::Whitespace,
-                Token::Number(String::from("45")),
-                Token::EOF,
+                (Token::Number("1"), (0, 1)),
+                (Token::Whitespace, (1, 2)),
+                (Token::Number("2"), (2, 3)),
+                (Token::Whitespace, (3, 4)),
+                (Token::Number("3"), (4, 5)),
+                (Token::Whitespace, (5, 6)),
+                (Token::Number("45"), (6, 8)),
+                (Token::EOF, (8, 8)),
             ]
         )
     }
@@ -210,7 +364,10 @@ mod tests {
         let input = "1.21";
         let result = lex(&input);
 
-        assert_eq!(result.unwrap(), vec![Token::Number(String::from("1.21")), Token::EOF])
+        assert_eq!(result.unwrap(), vec![
+            (Token::Number("1.21"), (0, 4)),
+            (Token::EOF, (4, 4)),
+        ])
     }
 
     #[test]
@@ -218,7 +375,28 @@ mod tests {
         let input = "1.1.1";
         let result = lex(&input);
 
-        assert_eq!(result.unwrap_err(), LexingError::InvalidChar('.'));
+        assert_eq!(result.unwrap_err(), LexingError::InvalidChar { ch: '.', position: 3 });
+    }
+
+    #[test]
+    fn minus_and_parens_parsed_correctly() {
+        let input = "(1 - 2)";
+
+        let result = lex(&input);
+
+        assert_eq!(
+            result.unwrap(),
+            vec![
+                (Token::LeftParen, (0, 1)),
+                (Token::Number("1"), (1, 2)),
+                (Token::Whitespace, (2, 3)),
+                (Token::Minus, (3, 4)),
+                (Token::Whitespace, (4, 5)),
+                (Token::Number("2"), (5, 6)),
+                (Token::RightParen, (6, 7)),
+                (Token::EOF, (7, 7)),
+            ]
+        );
     }
 
     #[test]
@@ -229,15 +407,57 @@ mod tests {
         assert_eq!(
             result.unwrap(),
             vec![
-                Token::Let,
-                Token::Whitespace,
-                Token::Identifier(String::from("someValue")),
-                Token::Whitespace,
-                Token::Equals,
-                Token::Whitespace,
-                Token::Number(String::from("10")),
-                Token::SemiColon,
-                Token::EOF,
+                (Token::Let, (0, 3)),
+                (Token::Whitespace, (3, 4)),
+                (Token::Identifier("someValue"), (4, 13)),
+                (Token::Whitespace, (13, 14)),
+                (Token::Equals, (14, 15)),
+                (Token::Whitespace, (15, 16)),
+                (Token::Number("10"), (16, 18)),
+                (Token::SemiColon, (18, 19)),
+                (Token::EOF, (19, 19)),
+            ]
+        );
+    }
+
+    #[test]
+    fn type_annotation_is_parsed_correctly() {
+        let input = "let x: int = 10;";
+        let result = lex(&input);
+
+        assert_eq!(
+            result.unwrap(),
+            vec![
+                (Token::Let, (0, 3)),
+                (Token::Whitespace, (3, 4)),
+                (Token::Identifier("x"), (4, 5)),
+                (Token::Colon, (5, 6)),
+                (Token::Whitespace, (6, 7)),
+                (Token::IntType, (7, 10)),
+                (Token::Whitespace, (10, 11)),
+                (Token::Equals, (11, 12)),
+                (Token::Whitespace, (12, 13)),
+                (Token::Number("10"), (13, 15)),
+                (Token::SemiColon, (15, 16)),
+                (Token::EOF, (16, 16)),
+            ]
+        );
+    }
+
+    #[test]
+    fn all_primitive_type_keywords_are_recognized() {
+        let input = "float string bool";
+        let result = lex(&input);
+
+        assert_eq!(
+            result.unwrap(),
+            vec![
+                (Token::FloatType, (0, 5)),
+                (Token::Whitespace, (5, 6)),
+                (Token::StringType, (6, 12)),
+                (Token::Whitespace, (12, 13)),
+                (Token::BoolType, (13, 17)),
+                (Token::EOF, (17, 17)),
             ]
         );
     }
@@ -1,3 +1,5 @@
+use std::borrow::Cow;
+
 use crate::lexer::Token;
 
 #[derive(Debug, PartialEq, Clone)]
@@ -13,28 +15,42 @@ enum Operator {
     Minus,
 }
 
+// A `let` binding's declared type, as distinct from `ValueType`: it names a type
+// rather than carrying one of its values, so it has no payload of its own.
+#[derive(Debug, PartialEq, Clone)]
+enum TypeAnnotation {
+    Integer,
+    Float,
+    String,
+    Bool,
+}
+
 #[derive(Debug, PartialEq, Clone)]
 enum Expression {
     Value { value: ValueType },
     Infix { left: Box<Expression>, operator: Operator, right: Box<Expression> },
-    Assignment { identifier: String, value: Box<Expression> },
+    Assignment { identifier: String, declared_type: Option<TypeAnnotation>, value: Box<Expression> },
 }
 
 #[derive(Debug, PartialEq, Clone)]
 enum ParsingError {
     NumberValueInvalid,
+    MissingRightParen,
+    UnexpectedOperator,
+    TypeMismatch,
     UnknownError,
 }
 
 type ParsingResult = Result<Vec<Expression>, ParsingError>;
 type CurrentToken = usize;
 type IntermediateParsingResult<'a> = Result<(CurrentToken, &'a mut Vec<Expression>), ParsingError>;
+type ExprParsingResult = Result<(CurrentToken, Expression), ParsingError>;
 
-fn parse(tokens: &mut Vec<Token>) -> ParsingResult {
+fn parse(tokens: &mut Vec<Token<'_>>) -> ParsingResult {
     return parse_recursive(tokens, 0, vec![].to_vec());
 }
 
-fn parse_recursive(tokens: &Vec<Token>, current: usize, mut output: Vec<Expression>) -> ParsingResult {
+fn parse_recursive(tokens: &Vec<Token<'_>>, current: usize, mut output: Vec<Expression>) -> ParsingResult {
     if current >= tokens.len() {
         return Ok(output.to_vec());
     }
@@ -45,33 +61,169 @@ fn parse_recursive(tokens: &Vec<Token>, current: usize, mut output: Vec<Expressi
     };
 }
 
-fn parse_next_expr<'a>(input: &Vec<Token>, current: usize, output: &'a mut Vec<Expression>) -> IntermediateParsingResult<'a> {
+fn parse_next_expr<'a>(input: &Vec<Token<'_>>, current: usize, output: &'a mut Vec<Expression>) -> IntermediateParsingResult<'a> {
     if current >= input.len() {
         return Ok((current, output));
     }
-    match input.get(current) {
-        None => Ok((current + 1, output)),
-        Some(token) =>
-            match token {
-                Token::Number(n) => parse_number_expr(n, current, output),
-                _ => Err(ParsingError::UnknownError)
+
+    // `let` is a statement form, not an operand, so it's dispatched here rather
+    // than through `parse_primary` — it must not be reachable inside an
+    // arithmetic expression or a parenthesized group.
+    let parsed = match input.get(current) {
+        Some(Token::Let) => parse_assignment_expr(input, current),
+        _ => parse_expr(input, current, 0, None),
+    };
+
+    return match parsed {
+        Err(e) => Err(e),
+        Ok((next_current, expr)) => {
+            output.push(expr);
+            Ok((next_current, output))
+        }
+    };
+}
+
+// Precedence-climbing (Pratt) parser: parses a primary expression, then folds in
+// any binary operators whose binding power is >= min_bp, recursing with
+// `op_bp + 1` on the right-hand side so equal-precedence operators associate left.
+// `declared_type` is threaded down to every number literal in the expression so a
+// `let` binding's annotation (if any) can be checked against the values it assigns.
+fn parse_expr(tokens: &Vec<Token<'_>>, current: usize, min_bp: u8, declared_type: Option<&TypeAnnotation>) -> ExprParsingResult {
+    let (mut current, mut left) = match parse_primary(tokens, current, declared_type) {
+        Err(e) => return Err(e),
+        Ok(result) => result,
+    };
+
+    loop {
+        let operator = match tokens.get(current) {
+            Some(Token::Plus) => Operator::Plus,
+            Some(Token::Minus) => Operator::Minus,
+            _ => break,
+        };
+
+        let op_bp = binding_power(&operator);
+        if op_bp < min_bp {
+            break;
+        }
+
+        match parse_expr(tokens, current + 1, op_bp + 1, declared_type) {
+            Err(e) => return Err(e),
+            Ok((next_current, right)) => {
+                left = Expression::Infix { left: Box::new(left), operator, right: Box::new(right) };
+                current = next_current;
             }
+        }
+    }
+
+    Ok((current, left))
+}
+
+fn binding_power(operator: &Operator) -> u8 {
+    match operator {
+        Operator::Plus => 1,
+        Operator::Minus => 1,
     }
 }
 
-fn parse_number_expr<'a>(value: &str, current: usize, output: &'a mut Vec<Expression>) -> IntermediateParsingResult<'a> {
-    return match value.parse::<i32>() {
-        Err(_) => Err(ParsingError::NumberValueInvalid),
-        Ok(value) => {
-            output.push(Expression::Value { value: ValueType::Integer(value) });
-            return Ok((current + 1, output));
+fn parse_primary(tokens: &Vec<Token<'_>>, current: usize, declared_type: Option<&TypeAnnotation>) -> ExprParsingResult {
+    match tokens.get(current) {
+        None => Err(ParsingError::UnknownError),
+        Some(Token::Number(n)) => parse_number_expr(n, current, declared_type),
+        Some(Token::String(s)) => parse_string_expr(s, current, declared_type),
+        Some(Token::LeftParen) => parse_grouped_expr(tokens, current, declared_type),
+        Some(_) => Err(ParsingError::UnexpectedOperator),
+    }
+}
+
+fn parse_number_expr(value: &str, current: usize, declared_type: Option<&TypeAnnotation>) -> ExprParsingResult {
+    let is_float_literal = value.contains('.');
+
+    if is_float_literal && matches!(declared_type, Some(TypeAnnotation::Integer)) {
+        return Err(ParsingError::TypeMismatch);
+    }
+
+    let parse_as_float = is_float_literal || matches!(declared_type, Some(TypeAnnotation::Float));
+
+    return if parse_as_float {
+        match value.parse::<f32>() {
+            Err(_) => Err(ParsingError::NumberValueInvalid),
+            Ok(value) => Ok((current + 1, Expression::Value { value: ValueType::Float(value) })),
+        }
+    } else {
+        match value.parse::<i32>() {
+            Err(_) => Err(ParsingError::NumberValueInvalid),
+            Ok(value) => Ok((current + 1, Expression::Value { value: ValueType::Integer(value) })),
+        }
+    };
+}
+
+fn parse_string_expr(value: &Cow<str>, current: usize, declared_type: Option<&TypeAnnotation>) -> ExprParsingResult {
+    if matches!(declared_type, Some(annotation) if *annotation != TypeAnnotation::String) {
+        return Err(ParsingError::TypeMismatch);
+    }
+
+    Ok((current + 1, Expression::Value { value: ValueType::String(value.to_string()) }))
+}
+
+fn parse_grouped_expr(tokens: &Vec<Token<'_>>, current: usize, declared_type: Option<&TypeAnnotation>) -> ExprParsingResult {
+    return match parse_expr(tokens, current + 1, 0, declared_type) {
+        Err(e) => Err(e),
+        Ok((next_current, expr)) => match tokens.get(next_current) {
+            Some(Token::RightParen) => Ok((next_current + 1, expr)),
+            _ => Err(ParsingError::MissingRightParen),
+        },
+    };
+}
+
+// `let` IDENTIFIER (`:` TYPE)? `=` EXPR `;`?
+fn parse_assignment_expr(tokens: &Vec<Token<'_>>, current: usize) -> ExprParsingResult {
+    let identifier_pos = current + 1;
+    let identifier = match tokens.get(identifier_pos) {
+        Some(Token::Identifier(name)) => name.to_string(),
+        _ => return Err(ParsingError::UnknownError),
+    };
+
+    let (after_annotation, declared_type) = match tokens.get(identifier_pos + 1) {
+        Some(Token::Colon) => match parse_type_annotation(tokens, identifier_pos + 2) {
+            Err(e) => return Err(e),
+            Ok((next, annotation)) => (next, Some(annotation)),
+        },
+        _ => (identifier_pos + 1, None),
+    };
+
+    match tokens.get(after_annotation) {
+        Some(Token::Equals) => {}
+        _ => return Err(ParsingError::UnknownError),
+    }
+
+    return match parse_expr(tokens, after_annotation + 1, 0, declared_type.as_ref()) {
+        Err(e) => Err(e),
+        Ok((next_current, value)) => {
+            let next_current = match tokens.get(next_current) {
+                Some(Token::SemiColon) => next_current + 1,
+                _ => next_current,
+            };
+
+            Ok((next_current, Expression::Assignment { identifier, declared_type, value: Box::new(value) }))
         }
     };
 }
 
+fn parse_type_annotation(tokens: &Vec<Token<'_>>, current: usize) -> Result<(usize, TypeAnnotation), ParsingError> {
+    match tokens.get(current) {
+        Some(Token::IntType) => Ok((current + 1, TypeAnnotation::Integer)),
+        Some(Token::FloatType) => Ok((current + 1, TypeAnnotation::Float)),
+        Some(Token::StringType) => Ok((current + 1, TypeAnnotation::String)),
+        Some(Token::BoolType) => Ok((current + 1, TypeAnnotation::Bool)),
+        _ => Err(ParsingError::UnknownError),
+    }
+}
+
+#[cfg(test)]
 mod test {
+    use std::borrow::Cow;
     use crate::lexer::Token;
-    use crate::parser::{Expression, parse, ValueType};
+    use crate::parser::{Expression, Operator, parse, ParsingError, TypeAnnotation, ValueType};
 
     extern crate speculoos;
 
@@ -79,9 +231,224 @@ mod test {
 
     #[test]
     fn number_token_parsed_as_value_expr() {
-        let result = parse(&mut vec![Token::Number("2".to_string())]);
+        let result = parse(&mut vec![Token::Number("2")]);
         let expected_result = vec![Expression::Value { value: ValueType::Integer(2) }];
 
         assert_that!(result).is_ok_containing(&expected_result);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn string_token_parsed_as_value_expr() {
+        let result = parse(&mut vec![Token::String(Cow::Borrowed("wow"))]);
+        let expected_result = vec![Expression::Value { value: ValueType::String(String::from("wow")) }];
+
+        assert_that!(result).is_ok_containing(&expected_result);
+    }
+
+    #[test]
+    fn single_plus_parsed_as_infix_expr() {
+        let result = parse(&mut vec![
+            Token::Number("1"),
+            Token::Plus,
+            Token::Number("2"),
+        ]);
+        let expected_result = vec![Expression::Infix {
+            left: Box::new(Expression::Value { value: ValueType::Integer(1) }),
+            operator: Operator::Plus,
+            right: Box::new(Expression::Value { value: ValueType::Integer(2) }),
+        }];
+
+        assert_that!(result).is_ok_containing(&expected_result);
+    }
+
+    #[test]
+    fn chained_plus_and_minus_are_left_associative() {
+        let result = parse(&mut vec![
+            Token::Number("1"),
+            Token::Plus,
+            Token::Number("2"),
+            Token::Minus,
+            Token::Number("3"),
+        ]);
+        let expected_result = vec![Expression::Infix {
+            left: Box::new(Expression::Infix {
+                left: Box::new(Expression::Value { value: ValueType::Integer(1) }),
+                operator: Operator::Plus,
+                right: Box::new(Expression::Value { value: ValueType::Integer(2) }),
+            }),
+            operator: Operator::Minus,
+            right: Box::new(Expression::Value { value: ValueType::Integer(3) }),
+        }];
+
+        assert_that!(result).is_ok_containing(&expected_result);
+    }
+
+    #[test]
+    fn parenthesized_group_overrides_associativity() {
+        let result = parse(&mut vec![
+            Token::Number("1"),
+            Token::Plus,
+            Token::LeftParen,
+            Token::Number("2"),
+            Token::Minus,
+            Token::Number("3"),
+            Token::RightParen,
+        ]);
+        let expected_result = vec![Expression::Infix {
+            left: Box::new(Expression::Value { value: ValueType::Integer(1) }),
+            operator: Operator::Plus,
+            right: Box::new(Expression::Infix {
+                left: Box::new(Expression::Value { value: ValueType::Integer(2) }),
+                operator: Operator::Minus,
+                right: Box::new(Expression::Value { value: ValueType::Integer(3) }),
+            }),
+        }];
+
+        assert_that!(result).is_ok_containing(&expected_result);
+    }
+
+    #[test]
+    fn missing_right_paren_is_an_error() {
+        let result = parse(&mut vec![
+            Token::LeftParen,
+            Token::Number("1"),
+        ]);
+
+        assert_that!(result).is_err_containing(&ParsingError::MissingRightParen);
+    }
+
+    #[test]
+    fn untyped_assignment_is_parsed_correctly() {
+        let result = parse(&mut vec![
+            Token::Let,
+            Token::Identifier("x"),
+            Token::Equals,
+            Token::Number("10"),
+            Token::SemiColon,
+        ]);
+        let expected_result = vec![Expression::Assignment {
+            identifier: String::from("x"),
+            declared_type: None,
+            value: Box::new(Expression::Value { value: ValueType::Integer(10) }),
+        }];
+
+        assert_that!(result).is_ok_containing(&expected_result);
+    }
+
+    #[test]
+    fn typed_assignment_is_parsed_correctly() {
+        let result = parse(&mut vec![
+            Token::Let,
+            Token::Identifier("x"),
+            Token::Colon,
+            Token::IntType,
+            Token::Equals,
+            Token::Number("10"),
+            Token::SemiColon,
+        ]);
+        let expected_result = vec![Expression::Assignment {
+            identifier: String::from("x"),
+            declared_type: Some(TypeAnnotation::Integer),
+            value: Box::new(Expression::Value { value: ValueType::Integer(10) }),
+        }];
+
+        assert_that!(result).is_ok_containing(&expected_result);
+    }
+
+    #[test]
+    fn float_literal_bound_to_int_annotation_is_a_type_mismatch() {
+        let result = parse(&mut vec![
+            Token::Let,
+            Token::Identifier("x"),
+            Token::Colon,
+            Token::IntType,
+            Token::Equals,
+            Token::Number("10.5"),
+            Token::SemiColon,
+        ]);
+
+        assert_that!(result).is_err_containing(&ParsingError::TypeMismatch);
+    }
+
+    #[test]
+    fn string_literal_bound_to_int_annotation_is_a_type_mismatch() {
+        let result = parse(&mut vec![
+            Token::Let,
+            Token::Identifier("x"),
+            Token::Colon,
+            Token::IntType,
+            Token::Equals,
+            Token::String(Cow::Borrowed("wow")),
+            Token::SemiColon,
+        ]);
+
+        assert_that!(result).is_err_containing(&ParsingError::TypeMismatch);
+    }
+
+    #[test]
+    fn string_literal_bound_to_string_annotation_is_parsed_correctly() {
+        let result = parse(&mut vec![
+            Token::Let,
+            Token::Identifier("x"),
+            Token::Colon,
+            Token::StringType,
+            Token::Equals,
+            Token::String(Cow::Borrowed("wow")),
+            Token::SemiColon,
+        ]);
+        let expected_result = vec![Expression::Assignment {
+            identifier: String::from("x"),
+            declared_type: Some(TypeAnnotation::String),
+            value: Box::new(Expression::Value { value: ValueType::String(String::from("wow")) }),
+        }];
+
+        assert_that!(result).is_ok_containing(&expected_result);
+    }
+
+    #[test]
+    fn let_nested_inside_an_infix_expression_is_an_error() {
+        let result = parse(&mut vec![
+            Token::Number("1"),
+            Token::Plus,
+            Token::Let,
+            Token::Identifier("x"),
+            Token::Equals,
+            Token::Number("2"),
+        ]);
+
+        assert_that!(result).is_err_containing(&ParsingError::UnexpectedOperator);
+    }
+
+    #[test]
+    fn let_nested_inside_a_parenthesized_group_is_an_error() {
+        let result = parse(&mut vec![
+            Token::LeftParen,
+            Token::Let,
+            Token::Identifier("y"),
+            Token::Equals,
+            Token::Number("3"),
+            Token::RightParen,
+        ]);
+
+        assert_that!(result).is_err_containing(&ParsingError::UnexpectedOperator);
+    }
+
+    #[test]
+    fn int_literal_bound_to_float_annotation_is_parsed_as_a_float() {
+        let result = parse(&mut vec![
+            Token::Let,
+            Token::Identifier("x"),
+            Token::Colon,
+            Token::FloatType,
+            Token::Equals,
+            Token::Number("10"),
+        ]);
+        let expected_result = vec![Expression::Assignment {
+            identifier: String::from("x"),
+            declared_type: Some(TypeAnnotation::Float),
+            value: Box::new(Expression::Value { value: ValueType::Float(10.0) }),
+        }];
+
+        assert_that!(result).is_ok_containing(&expected_result);
+    }
+}